@@ -0,0 +1,301 @@
+#!/usr/bin/env python3
+# 从GNU Unifont的unifont.hex生成程序内置的后备字体(TrueType)
+# 用法: python3 make_unifont_subset.py unifont.hex ../../src/main.rs unifont-subset.ttf
+#
+# 收录的字符: GB2312中的全部汉字和符号、CJK标点、全角字符,以及src/main.rs中出现的所有非ASCII字符。
+# 每个16x16点阵的像素被合并成矩形轮廓,因此字形在任何字号下都保持点阵风格。
+import struct
+import sys
+
+PIXEL = 16  # 每个像素的字体单位
+UNITS_PER_EM = 16 * PIXEL
+ASCENT = 14 * PIXEL  # Unifont的基线在第14行下方
+DESCENT = 2 * PIXEL
+
+
+def read_hex(path):
+    glyphs = {}
+    with open(path) as hex_file:
+        for line in hex_file:
+            code, bitmap = line.strip().split(":")
+            code = int(code, 16)
+            if code <= 0xFFFF:
+                glyphs[code] = bitmap
+    return glyphs
+
+
+def wanted_chars(source_path):
+    chars = set()
+    for high in range(0xA1, 0xF8):
+        for low in range(0xA1, 0xFF):
+            try:
+                chars.add(ord(bytes([high, low]).decode("gb2312")))
+            except UnicodeDecodeError:
+                pass
+    chars.update(range(0x3000, 0x3040))
+    chars.update(range(0xFF00, 0xFFF0))
+    with open(source_path, encoding="utf-8") as source:
+        chars.update(ord(c) for c in source.read() if ord(c) > 0x7F)
+    return chars
+
+
+def rectangles(bitmap):
+    # 宽字符每行4个十六进制数(16像素),窄字符每行2个(8像素)
+    width = len(bitmap) // 16 * 4
+    rows = []
+    for row in range(16):
+        bits = int(bitmap[row * width // 4:(row + 1) * width // 4], 16)
+        runs = []
+        x = 0
+        while x < width:
+            if bits >> (width - 1 - x) & 1:
+                start = x
+                while x < width and bits >> (width - 1 - x) & 1:
+                    x += 1
+                runs.append((start, x))
+            else:
+                x += 1
+        rows.append(runs)
+    # 上下相邻且左右范围相同的横条合并成一个矩形
+    rects = []
+    open_rects = {}
+    for row, runs in enumerate(rows + [[]]):
+        still_open = {}
+        for run in runs:
+            top = open_rects.pop(run, row)
+            still_open[run] = top
+        for (x0, x1), top in open_rects.items():
+            rects.append((x0, top, x1, row))
+        open_rects = still_open
+    return width, rects
+
+
+def encode_glyph(rects):
+    if not rects:
+        return b"", (0, 0, 0, 0), 0, 0
+    points = []
+    end_points = []
+    for x0, top, x1, bottom in rects:
+        # TrueType中填充的外轮廓为顺时针,y轴向上
+        y_top = ASCENT - top * PIXEL
+        y_bottom = ASCENT - bottom * PIXEL
+        points += [
+            (x0 * PIXEL, y_bottom),
+            (x0 * PIXEL, y_top),
+            (x1 * PIXEL, y_top),
+            (x1 * PIXEL, y_bottom),
+        ]
+        end_points.append(len(points) - 1)
+    xs = [x for x, _ in points]
+    ys = [y for _, y in points]
+    bbox = (min(xs), min(ys), max(xs), max(ys))
+
+    flags = bytearray()
+    x_data = bytearray()
+    y_data = bytearray()
+    last_x = last_y = 0
+    for x, y in points:
+        flag = 0x01
+        dx, dy = x - last_x, y - last_y
+        last_x, last_y = x, y
+        if dx == 0:
+            flag |= 0x10
+        elif -255 <= dx <= 255:
+            flag |= 0x02 | (0x10 if dx > 0 else 0)
+            x_data.append(abs(dx))
+        else:
+            x_data += struct.pack(">h", dx)
+        if dy == 0:
+            flag |= 0x20
+        elif -255 <= dy <= 255:
+            flag |= 0x04 | (0x20 if dy > 0 else 0)
+            y_data.append(abs(dy))
+        else:
+            y_data += struct.pack(">h", dy)
+        flags.append(flag)
+
+    data = struct.pack(">hhhhh", len(end_points), *bbox)
+    data += b"".join(struct.pack(">H", end) for end in end_points)
+    data += struct.pack(">H", 0) + bytes(flags) + bytes(x_data) + bytes(y_data)
+    if len(data) % 2:
+        data += b"\0"
+    return data, bbox, len(points), len(end_points)
+
+
+def cmap_table(codes):
+    # 格式4,连续的码位对应连续的字形编号,每段只需要一个idDelta
+    segments = []
+    for glyph_id, code in enumerate(codes, start=1):
+        if segments and segments[-1][1] == code - 1 and segments[-1][2] == glyph_id - (code - segments[-1][0]):
+            segments[-1][1] = code
+        else:
+            segments.append([code, code, glyph_id])
+    segments.append([0xFFFF, 0xFFFF, 0])
+    count = len(segments)
+    search_range = 2 ** (count.bit_length() - 1) * 2
+    subtable = struct.pack(
+        ">HHHHHHH",
+        4,
+        16 + count * 8,
+        0,
+        count * 2,
+        search_range,
+        count.bit_length() - 1,
+        count * 2 - search_range,
+    )
+    subtable += b"".join(struct.pack(">H", end) for _, end, _ in segments)
+    subtable += struct.pack(">H", 0)
+    subtable += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    subtable += b"".join(
+        struct.pack(">H", (glyph_id - start) & 0xFFFF if glyph_id else 1)
+        for start, _, glyph_id in segments
+    )
+    subtable += b"\0\0" * count
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def name_table(names):
+    records = b""
+    strings = b""
+    for name_id, text in names.items():
+        encoded = text.encode("utf-16-be")
+        records += struct.pack(">HHHHHH", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    return struct.pack(">HHH", 0, len(names), 6 + len(records)) + records + strings
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def build_font(glyphs, chars, output_path):
+    codes = sorted(code for code in chars if code in glyphs)
+    glyf = bytearray()
+    loca = [0]
+    metrics = []
+    max_points = max_contours = 0
+    x_min = y_min = x_max = y_max = 0
+    # 0号字形.notdef为空
+    loca.append(0)
+    metrics.append((UNITS_PER_EM // 2, 0))
+    for code in codes:
+        width, rects = rectangles(glyphs[code])
+        data, bbox, points, contours = encode_glyph(rects)
+        glyf += data
+        loca.append(len(glyf))
+        metrics.append((width * PIXEL, bbox[0]))
+        max_points = max(max_points, points)
+        max_contours = max(max_contours, contours)
+        if data:
+            x_min, y_min = min(x_min, bbox[0]), min(y_min, bbox[1])
+            x_max, y_max = max(x_max, bbox[2]), max(y_max, bbox[3])
+    glyph_count = len(codes) + 1
+
+    tables = {}
+    tables[b"head"] = struct.pack(
+        ">IIIIHHqqhhhhHHhhh",
+        0x00010000,
+        0x00010000,
+        0,
+        0x5F0F3CF5,
+        0x000B,
+        UNITS_PER_EM,
+        0,
+        0,
+        x_min,
+        y_min,
+        x_max,
+        y_max,
+        0,
+        8,
+        2,
+        1,
+        0,
+    )
+    tables[b"hhea"] = struct.pack(
+        ">IhhhHhhhhhhhhhhhH",
+        0x00010000,
+        ASCENT,
+        -DESCENT,
+        0,
+        UNITS_PER_EM,
+        0,
+        0,
+        x_max,
+        1,
+        0,
+        0,
+        0, 0, 0, 0,
+        0,
+        glyph_count,
+    )
+    tables[b"maxp"] = struct.pack(
+        ">IHHHHHHHHHHHHHH",
+        0x00010000,
+        glyph_count,
+        max_points,
+        max_contours,
+        0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+    )
+    tables[b"hmtx"] = b"".join(struct.pack(">Hh", advance, lsb) for advance, lsb in metrics)
+    tables[b"loca"] = b"".join(struct.pack(">I", offset) for offset in loca)
+    tables[b"glyf"] = bytes(glyf)
+    tables[b"cmap"] = cmap_table(codes)
+    tables[b"post"] = struct.pack(">IIhhIIIII", 0x00030000, 0, -PIXEL, PIXEL, 0, 0, 0, 0, 0)
+    family = "Gitpull Unifont Subset"
+    tables[b"name"] = name_table({
+        0: "Glyphs from GNU Unifont 15.1.05 by Roman Czyborra, Paul Hardy et al., "
+           "SIL Open Font License 1.1",
+        1: family,
+        2: "Regular",
+        4: family,
+        5: "Version 15.1.05",
+        6: "GitpullUnifontSubset-Regular",
+    })
+    tables[b"OS/2"] = struct.pack(
+        ">HhHHHhhhhhhhhhhh10sIIII4sHHHhhhHH",
+        1,
+        UNITS_PER_EM // 2,
+        400,
+        5,
+        0,
+        PIXEL * 8, PIXEL * 8, 0, PIXEL,
+        PIXEL * 8, PIXEL * 8, 0, PIXEL * 6,
+        PIXEL, PIXEL * 8,
+        0,
+        b"\0" * 10,
+        0, 0x00040000 | 0x00020000, 0, 0,
+        b"UNFT",
+        0x40,
+        codes[0],
+        codes[-1],
+        ASCENT,
+        -DESCENT,
+        0,
+        ASCENT,
+        DESCENT,
+    ) + struct.pack(">II", 0x00040000, 0)
+
+    tags = sorted(tables)
+    offset = 12 + 16 * len(tags)
+    directory = struct.pack(">IHHHH", 0x00010000, len(tags), 128, 3, len(tags) * 16 - 128)
+    body = b""
+    for tag in tags:
+        data = tables[tag]
+        directory += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    font = bytearray(directory + body)
+    # head表中的checkSumAdjustment
+    head_offset = 12 + 16 * tags.index(b"head")
+    head_start = struct.unpack(">I", font[head_offset + 8:head_offset + 12])[0]
+    adjustment = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+    font[head_start + 8:head_start + 12] = struct.pack(">I", adjustment)
+    with open(output_path, "wb") as output:
+        output.write(font)
+    print("%d glyphs, %d bytes" % (glyph_count, len(font)))
+
+
+if __name__ == "__main__":
+    hex_path, source_path, output_path = sys.argv[1:4]
+    build_font(read_hex(hex_path), wanted_chars(source_path), output_path)